@@ -1,5 +1,5 @@
 use reqwest::Client;
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::collections::HashSet;
 use dotenv::dotenv;
 use std::error::Error;
@@ -20,6 +20,26 @@ struct AuthToken {
     access_token: String,
     refresh_token: String,
     expires_in: u32,
+    scope: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RefreshedToken {
+    access_token: String,
+    expires_in: u32,
+    refresh_token: Option<String>,
+    scope: Option<String>,
+}
+
+// Cached on disk so we don't make the user re-authorize in their browser on
+// every run. expires_at is a Unix timestamp. scope is the space-separated
+// list of OAuth scopes the cached token was actually granted.
+#[derive(Debug, Serialize, Deserialize)]
+struct TokenCache {
+    access_token: String,
+    refresh_token: String,
+    expires_at: i64,
+    scope: String,
 }
 
 #[derive(Debug, Serialize)]
@@ -55,6 +75,99 @@ struct SpotifyAlbum {
     uri: String,
 }
 
+#[derive(Debug, Deserialize)]
+struct AlbumTracksResponse {
+    items: Vec<SpotifyTrack>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SpotifyTrack {
+    uri: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SpotifyArtistRef {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SpotifyTrackDetails {
+    artists: Vec<SpotifyArtistRef>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SpotifyAlbumDetails {
+    artists: Vec<SpotifyArtistRef>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SpotifyArtistDetails {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SpotifyTopArtistsResponse {
+    items: Vec<SpotifyArtistRef>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SpotifyTopTracksResponse {
+    items: Vec<SpotifyTrackDetails>,
+}
+
+// Spotify's /me/top/* endpoints accept this recency window via time_range.
+#[derive(Debug, Clone, Copy)]
+enum TopTimeRange {
+    Short,
+    Medium,
+    Long,
+}
+
+impl TopTimeRange {
+    fn as_spotify_param(self) -> &'static str {
+        match self {
+            TopTimeRange::Short => "short_term",
+            TopTimeRange::Medium => "medium_term",
+            TopTimeRange::Long => "long_term",
+        }
+    }
+
+    fn parse(input: &str) -> Option<TopTimeRange> {
+        match input {
+            "short" | "short_term" => Some(TopTimeRange::Short),
+            "medium" | "medium_term" => Some(TopTimeRange::Medium),
+            "long" | "long_term" => Some(TopTimeRange::Long),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SpotifySeedKind {
+    Track,
+    Album,
+    Artist,
+}
+
+trait Paginated {
+    type Item;
+    fn into_items(self) -> Vec<Self::Item>;
+}
+
+impl Paginated for SpotifySearchResponse {
+    type Item = SpotifyAlbum;
+    fn into_items(self) -> Vec<SpotifyAlbum> {
+        self.albums.items
+    }
+}
+
+impl Paginated for AlbumTracksResponse {
+    type Item = SpotifyTrack;
+    fn into_items(self) -> Vec<SpotifyTrack> {
+        self.items
+    }
+}
+
 // Simplified Last.fm structures
 #[derive(Debug, Deserialize)]
 struct TopAlbums {
@@ -92,11 +205,100 @@ struct SimilarArtist {
     name: String,
 }
 
+fn token_cache_path() -> Result<std::path::PathBuf, Box<dyn Error>> {
+    let mut path = dirs::config_dir().ok_or("Could not determine config directory")?;
+    path.push("last-spot");
+    std::fs::create_dir_all(&path)?;
+    path.push("token_cache.json");
+    Ok(path)
+}
+
+fn load_token_cache() -> Option<TokenCache> {
+    let path = token_cache_path().ok()?;
+    let contents = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+fn save_token_cache(cache: &TokenCache) -> Result<(), Box<dyn Error>> {
+    let path = token_cache_path()?;
+    std::fs::write(&path, serde_json::to_string_pretty(cache)?)?;
+
+    // The refresh token doesn't expire on its own, so keep this file readable
+    // only by the owner.
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+    }
+
+    Ok(())
+}
+
+// Exchanges a refresh token for a new access token, updating the on-disk cache.
+// existing_scope is carried over since Spotify doesn't always echo `scope` on refresh.
+async fn refresh_spotify_token(
+    client_id: &str,
+    client_secret: &str,
+    refresh_token: &str,
+    existing_scope: &str,
+) -> Result<String, Box<dyn Error>> {
+    let client = Client::new();
+    let auth = general_purpose::STANDARD.encode(format!("{}:{}", client_id, client_secret));
+
+    let response = client
+        .post("https://accounts.spotify.com/api/token")
+        .header("Authorization", format!("Basic {}", auth))
+        .form(&[
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token),
+        ])
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        let error_text = response.text().await?;
+        return Err(format!("Failed to refresh token: {}", error_text).into());
+    }
+
+    let refreshed: RefreshedToken = response.json().await?;
+    let cache = TokenCache {
+        access_token: refreshed.access_token.clone(),
+        // Spotify doesn't always issue a new refresh token; keep the old one if absent.
+        refresh_token: refreshed.refresh_token.unwrap_or_else(|| refresh_token.to_string()),
+        expires_at: chrono::Utc::now().timestamp() + refreshed.expires_in as i64,
+        scope: refreshed.scope.unwrap_or_else(|| existing_scope.to_string()),
+    };
+    save_token_cache(&cache)?;
+
+    Ok(cache.access_token)
+}
+
 async fn get_spotify_auth_token(
     client_id: &str,
     client_secret: &str,
     redirect_uri: &str,
 ) -> Result<String, Box<dyn Error>> {
+    // Scopes a usable token must carry; also requested in the authorization URL
+    let scopes = ["playlist-modify-private", "playlist-modify-public", "user-top-read"];
+
+    if let Some(cache) = load_token_cache() {
+        let granted: HashSet<&str> = cache.scope.split_whitespace().collect();
+        let has_required_scopes = scopes.iter().all(|scope| granted.contains(scope));
+
+        if !has_required_scopes {
+            println!("🔐 Cached token is missing required scopes, re-authorizing...");
+        } else if cache.expires_at > chrono::Utc::now().timestamp() + 30 {
+            println!("🔑 Using cached Spotify token");
+            return Ok(cache.access_token);
+        } else if !cache.refresh_token.is_empty() {
+            println!("🔄 Refreshing expired Spotify token...");
+            match refresh_spotify_token(client_id, client_secret, &cache.refresh_token, &cache.scope).await {
+                Ok(access_token) => return Ok(access_token),
+                Err(e) => println!("Warning: failed to refresh token ({}), re-authorizing...", e),
+            }
+        }
+    }
+
     // Generate a random state string
     let state: String = rand::thread_rng()
         .sample_iter(&rand::distributions::Alphanumeric)
@@ -108,8 +310,6 @@ async fn get_spotify_auth_token(
     let listener = TcpListener::bind("127.0.0.1:8888")?;
     println!("Started local server on port 8888");
 
-    // Construct the authorization URL
-    let scopes = ["playlist-modify-private", "playlist-modify-public"];
     let auth_url = format!(
         "https://accounts.spotify.com/authorize?client_id={}\
          &response_type=code\
@@ -175,41 +375,109 @@ async fn get_spotify_auth_token(
     }
 
     let auth_token: AuthToken = token_response.json().await?;
+
+    save_token_cache(&TokenCache {
+        access_token: auth_token.access_token.clone(),
+        refresh_token: auth_token.refresh_token.clone(),
+        expires_at: chrono::Utc::now().timestamp() + auth_token.expires_in as i64,
+        scope: auth_token.scope.clone(),
+    })?;
+
     Ok(auth_token.access_token)
 }
 
-async fn get_recommendations(
+// Spotify paginates most list endpoints in chunks of up to 50 items.
+const CHUNK_SIZE: u32 = 50;
+
+async fn spotify_get<T: DeserializeOwned>(
     client: &Client,
-    username: &str,
-    api_key: &str,
-) -> Result<Vec<(String, String)>, Box<dyn Error>> {
-    let mut recommendations = Vec::new();
-    let mut seen_artists = HashSet::new();
-    
-    // Get top albums from last 6 months only
-    let url = format!(
-        "http://ws.audioscrobbler.com/2.0/?method=user.gettopalbums&user={}&api_key={}&format=json&period=6month&limit=10",
-        username, api_key
-    );
-    
-    println!("📊 Fetching your top albums...");
-    let top_albums: TopAlbums = client.get(&url).send().await?.json().await?;
-    
-    // Process each top artist
-    for album in &top_albums.topalbums.album {
-        if seen_artists.contains(&album.artist.name) {
+    url: &str,
+    token: &str,
+) -> Result<T, Box<dyn Error>> {
+    loop {
+        let response = client
+            .get(url)
+            .header("Authorization", format!("Bearer {}", token))
+            .send()
+            .await?;
+
+        // Retry on 429, honoring Retry-After (default 5s).
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = response
+                .headers()
+                .get("Retry-After")
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(5);
+            println!("⏳ Rate limited by Spotify, waiting {}s...", retry_after);
+            tokio::time::sleep(std::time::Duration::from_secs(retry_after)).await;
             continue;
         }
-        seen_artists.insert(album.artist.name.clone());
-        
-        println!("🔍 Finding similar artists to: {}", album.artist.name);
-        
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await?;
+            return Err(format!("Spotify request failed. Status: {}, Error: {}", status, error_text).into());
+        }
+
+        return Ok(response.json::<T>().await?);
+    }
+}
+
+async fn fetch_all_pages<T>(
+    client: &Client,
+    token: &str,
+    mut make_url: impl FnMut(u32, u32) -> String,
+) -> Result<Vec<T::Item>, Box<dyn Error>>
+where
+    T: Paginated + DeserializeOwned,
+{
+    let mut all_items = Vec::new();
+    let mut offset = 0u32;
+
+    loop {
+        let url = make_url(offset, CHUNK_SIZE);
+        let page: T = spotify_get(client, &url, token).await?;
+        let items = page.into_items();
+
+        if items.is_empty() {
+            break;
+        }
+
+        let page_len = items.len() as u32;
+        all_items.extend(items);
+        offset += CHUNK_SIZE;
+
+        if page_len < CHUNK_SIZE {
+            break;
+        }
+    }
+
+    Ok(all_items)
+}
+
+const MAX_RECOMMENDATIONS: usize = 10;
+
+async fn expand_similar_artists(
+    client: &Client,
+    api_key: &str,
+    seed_artists: &[String],
+    recommendations: &mut Vec<(String, String)>,
+    max_recommendations: usize,
+) -> Result<(), Box<dyn Error>> {
+    for seed_artist in seed_artists {
+        if recommendations.len() >= max_recommendations {
+            break;
+        }
+
+        println!("🔍 Finding similar artists to: {}", seed_artist);
+
         // Get similar artists
         let similar_url = format!(
             "http://ws.audioscrobbler.com/2.0/?method=artist.getsimilar&artist={}&api_key={}&format=json&limit=5",
-            urlencoding::encode(&album.artist.name), api_key
+            urlencoding::encode(seed_artist), api_key
         );
-        
+
         if let Ok(similar) = client.get(&similar_url).send().await?.json::<SimilarArtists>().await {
             // Get top album from each similar artist
             for similar_artist in similar.similarartists.artist.iter().take(2) {
@@ -217,33 +485,279 @@ async fn get_recommendations(
                     "http://ws.audioscrobbler.com/2.0/?method=artist.gettopalbums&artist={}&api_key={}&format=json&limit=1",
                     urlencoding::encode(&similar_artist.name), api_key
                 );
-                
+
                 if let Ok(artist_albums) = client.get(&artist_albums_url).send().await?.json::<TopAlbums>().await {
                     if let Some(top_album) = artist_albums.topalbums.album.first() {
                         recommendations.push((similar_artist.name.clone(), top_album.name.clone()));
                         println!("✓ Added recommendation: {} - {}", similar_artist.name, top_album.name);
-                        
-                        if recommendations.len() >= 10 {
-                            return Ok(recommendations);
+
+                        if recommendations.len() >= max_recommendations {
+                            return Ok(());
                         }
                     }
                 }
             }
         }
     }
-    
+
+    Ok(())
+}
+
+async fn get_top_albums(client: &Client, username: &str, api_key: &str) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+    let url = format!(
+        "http://ws.audioscrobbler.com/2.0/?method=user.gettopalbums&user={}&api_key={}&format=json&period=6month&limit=10",
+        username, api_key
+    );
+
+    let top_albums: TopAlbums = client.get(&url).send().await?.json().await?;
+
+    Ok(top_albums
+        .topalbums
+        .album
+        .iter()
+        .map(|album| (album.artist.name.clone(), album.name.clone()))
+        .collect())
+}
+
+async fn get_recommendations(
+    client: &Client,
+    username: &str,
+    api_key: &str,
+    extra_seed_artists: &[String],
+) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+    let mut recommendations = Vec::new();
+    let mut seen_artists = HashSet::new();
+
+    println!("📊 Fetching your top albums...");
+    let top_albums = get_top_albums(client, username, api_key).await?;
+
+    let mut seed_artists: Vec<String> = top_albums
+        .iter()
+        .map(|(artist, _)| artist.clone())
+        .filter(|name| seen_artists.insert(name.clone()))
+        .collect();
+
+    seed_artists.extend(
+        extra_seed_artists
+            .iter()
+            .filter(|name| seen_artists.insert((*name).clone()))
+            .cloned(),
+    );
+
+    expand_similar_artists(client, api_key, &seed_artists, &mut recommendations, MAX_RECOMMENDATIONS).await?;
+
     Ok(recommendations)
 }
 
+async fn get_recommendations_from_artist(
+    client: &Client,
+    seed_artist: &str,
+    api_key: &str,
+) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+    let mut recommendations = Vec::new();
+
+    expand_similar_artists(
+        client,
+        api_key,
+        &[seed_artist.to_string()],
+        &mut recommendations,
+        MAX_RECOMMENDATIONS,
+    )
+    .await?;
+
+    Ok(recommendations)
+}
+
+async fn get_artist_orbit(
+    client: &Client,
+    username: &str,
+    api_key: &str,
+) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+    println!("📊 Fetching top albums for {}...", username);
+    let top_albums = get_top_albums(client, username, api_key).await?;
+
+    let mut seen_artists = HashSet::new();
+    let seed_artists: Vec<String> = top_albums
+        .iter()
+        .map(|(artist, _)| artist.clone())
+        .filter(|name| seen_artists.insert(name.clone()))
+        .collect();
+
+    let mut orbit = top_albums;
+    expand_similar_artists(client, api_key, &seed_artists, &mut orbit, ORBIT_SIZE).await?;
+
+    Ok(orbit)
+}
+
+// listener_count is how many listeners' orbits an artist appeared in;
+// rank_weight is a tiebreaker bonus for showing up early.
+struct BlendedScore {
+    album: String,
+    listener_count: u32,
+    rank_weight: u32,
+}
+
+// Blending draws from a larger per-listener orbit than a single-user playlist
+// so there's enough overlap between listeners to intersect against.
+const ORBIT_SIZE: usize = 40;
+
+fn score_blended_candidates(orbits: &[Vec<(String, String)>]) -> std::collections::HashMap<String, BlendedScore> {
+    let mut scores: std::collections::HashMap<String, BlendedScore> = std::collections::HashMap::new();
+
+    for orbit in orbits {
+        let mut seen_in_orbit = HashSet::new();
+
+        for (rank, (artist, album)) in orbit.iter().enumerate() {
+            if !seen_in_orbit.insert(artist.clone()) {
+                continue;
+            }
+
+            let rank_bonus = (orbit.len() - rank) as u32;
+            let entry = scores.entry(artist.clone()).or_insert_with(|| BlendedScore {
+                album: album.clone(),
+                listener_count: 0,
+                rank_weight: 0,
+            });
+            entry.listener_count += 1;
+            entry.rank_weight += rank_bonus;
+        }
+    }
+
+    scores
+}
+
+// Keeps only artists that appeared in at least two listeners' orbits.
+async fn get_blended_recommendations(
+    client: &Client,
+    usernames: &[String],
+    api_key: &str,
+) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+    let mut orbits = Vec::with_capacity(usernames.len());
+    for username in usernames {
+        orbits.push(get_artist_orbit(client, username, api_key).await?);
+    }
+
+    let scores = score_blended_candidates(&orbits);
+
+    let mut shared: Vec<(String, BlendedScore)> = scores
+        .into_iter()
+        .filter(|(_, score)| score.listener_count >= 2)
+        .collect();
+
+    shared.sort_by(|a, b| {
+        b.1.listener_count
+            .cmp(&a.1.listener_count)
+            .then(b.1.rank_weight.cmp(&a.1.rank_weight))
+    });
+
+    Ok(shared
+        .into_iter()
+        .take(MAX_RECOMMENDATIONS)
+        .map(|(artist, score)| (artist, score.album))
+        .collect())
+}
+
+// Parses e.g. https://open.spotify.com/album/4aawyAB9vmqN3uQ7FjRGTy?si=...,
+// discarding the si= query suffix.
+fn parse_spotify_seed_url(input: &str) -> Option<(SpotifySeedKind, String)> {
+    let url = Url::parse(input).ok()?;
+    let mut segments = url.path_segments()?;
+
+    let kind = match segments.next()? {
+        "track" => SpotifySeedKind::Track,
+        "album" => SpotifySeedKind::Album,
+        "artist" => SpotifySeedKind::Artist,
+        _ => return None,
+    };
+
+    let id = segments.next()?.split('?').next()?.to_string();
+    if id.is_empty() {
+        return None;
+    }
+
+    Some((kind, id))
+}
+
+// Requires the user-top-read scope.
+async fn get_spotify_top_artists(
+    client: &Client,
+    token: &str,
+    time_range: TopTimeRange,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let url = format!(
+        "https://api.spotify.com/v1/me/top/artists?time_range={}&limit=10",
+        time_range.as_spotify_param()
+    );
+    let response: SpotifyTopArtistsResponse = spotify_get(client, &url, token).await?;
+    Ok(response.items.into_iter().map(|artist| artist.name).collect())
+}
+
+async fn get_spotify_top_track_artists(
+    client: &Client,
+    token: &str,
+    time_range: TopTimeRange,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let url = format!(
+        "https://api.spotify.com/v1/me/top/tracks?time_range={}&limit=10",
+        time_range.as_spotify_param()
+    );
+    let response: SpotifyTopTracksResponse = spotify_get(client, &url, token).await?;
+    Ok(response
+        .items
+        .into_iter()
+        .flat_map(|track| track.artists.into_iter().map(|artist| artist.name))
+        .collect())
+}
+
+async fn resolve_seed_artist_name(
+    client: &Client,
+    token: &str,
+    kind: SpotifySeedKind,
+    id: &str,
+) -> Result<String, Box<dyn Error>> {
+    match kind {
+        SpotifySeedKind::Track => {
+            let track: SpotifyTrackDetails = spotify_get(
+                client,
+                &format!("https://api.spotify.com/v1/tracks/{}", id),
+                token,
+            )
+            .await?;
+            track.artists.into_iter().next().map(|a| a.name).ok_or_else(|| "Track has no artists".into())
+        }
+        SpotifySeedKind::Album => {
+            let album: SpotifyAlbumDetails = spotify_get(
+                client,
+                &format!("https://api.spotify.com/v1/albums/{}", id),
+                token,
+            )
+            .await?;
+            album.artists.into_iter().next().map(|a| a.name).ok_or_else(|| "Album has no artists".into())
+        }
+        SpotifySeedKind::Artist => {
+            let artist: SpotifyArtistDetails = spotify_get(
+                client,
+                &format!("https://api.spotify.com/v1/artists/{}", id),
+                token,
+            )
+            .await?;
+            Ok(artist.name)
+        }
+    }
+}
+
 async fn create_spotify_playlist(
     token: &str,
     user_id: &str,
+    playlist_name: &str,
+    playlist_description: &str,
     recommendations: &[(String, String)],
+    full_album: bool,
 ) -> Result<String, Box<dyn Error>> {
     let client = Client::new();
-    
+
     println!("Creating playlist...");
-    
+
     // Create playlist
     let playlist_response = client
         .post(&format!(
@@ -253,8 +767,8 @@ async fn create_spotify_playlist(
         .header("Authorization", format!("Bearer {}", token))
         .header("Content-Type", "application/json")
         .json(&CreatePlaylistRequest {
-            name: format!("Last.fm Discoveries - {}", chrono::Local::now().format("%Y-%m-%d")),
-            description: "Fresh music recommendations based on your Last.fm history".to_string(),
+            name: playlist_name.to_string(),
+            description: playlist_description.to_string(),
             public: false,
         })
         .send()
@@ -278,68 +792,42 @@ async fn create_spotify_playlist(
     
     for (artist, album) in recommendations {
         let query = format!("album:{} artist:{}", album, artist);
-        let search_url = format!(
-            "https://api.spotify.com/v1/search?q={}&type=album,track&limit=1",
-            urlencoding::encode(&query)
-        );
-        
-        let search_response = client
-            .get(&search_url)
-            .header("Authorization", format!("Bearer {}", token))
-            .send()
-            .await?;
 
-        let status = search_response.status();
-        if !status.is_success() {
+        let albums = fetch_all_pages::<SpotifySearchResponse>(&client, token, |offset, limit| {
+            format!(
+                "https://api.spotify.com/v1/search?q={}&type=album&limit={}&offset={}",
+                urlencoding::encode(&query),
+                limit,
+                offset
+            )
+        })
+        .await?;
+
+        let Some(album_result) = albums.first() else {
             println!("Warning: Search failed for {} - {}", artist, album);
             continue;
-        }
-
-        #[derive(Debug, Deserialize)]
-        struct SearchResponse {
-            tracks: Option<TracksResponse>,
-            albums: SpotifyAlbums,
-        }
+        };
+
+        let album_id = album_result.uri.split(':').last().unwrap_or("");
+        let album_tracks = fetch_all_pages::<AlbumTracksResponse>(&client, token, |offset, limit| {
+            format!(
+                "https://api.spotify.com/v1/albums/{}/tracks?limit={}&offset={}",
+                album_id, limit, offset
+            )
+        })
+        .await?;
 
-        #[derive(Debug, Deserialize)]
-        struct TracksResponse {
-            items: Vec<Track>,
+        if album_tracks.is_empty() {
+            println!("Warning: No tracks found for {} - {}", artist, album);
+            continue;
         }
 
-        #[derive(Debug, Deserialize)]
-        struct Track {
-            uri: String,
-        }
-
-        let search_result: SearchResponse = search_response.json().await?;
-        
-        // Try to get the first track from the album
-        if let Some(tracks) = search_result.tracks {
-            if let Some(track) = tracks.items.first() {
-                track_uris.push(track.uri.clone());
-                println!("Found track on Spotify: {} - {}", artist, album);
-            }
-        } else if let Some(album_result) = search_result.albums.items.first() {
-            // If no track found, get tracks from the album
-            let album_tracks_url = format!(
-                "https://api.spotify.com/v1/albums/{}/tracks?limit=1",
-                album_result.uri.split(":").last().unwrap_or("")
-            );
-            
-            let tracks_response = client
-                .get(&album_tracks_url)
-                .header("Authorization", format!("Bearer {}", token))
-                .send()
-                .await;
-
-            if let Ok(response) = tracks_response {
-                if let Ok(tracks) = response.json::<TracksResponse>().await {
-                    if let Some(track) = tracks.items.first() {
-                        track_uris.push(track.uri.clone());
-                        println!("Found album track on Spotify: {} - {}", artist, album);
-                    }
-                }
-            }
+        if full_album {
+            track_uris.extend(album_tracks.iter().map(|track| track.uri.clone()));
+            println!("Found {} album tracks on Spotify: {} - {}", album_tracks.len(), artist, album);
+        } else if let Some(track) = album_tracks.first() {
+            track_uris.push(track.uri.clone());
+            println!("Found album track on Spotify: {} - {}", artist, album);
         }
     }
     
@@ -377,39 +865,86 @@ async fn main() -> Result<(), Box<dyn Error>> {
     dotenv().ok();
     
     let lastfm_key = std::env::var("LASTFM_API_KEY")?;
-    let lastfm_user = std::env::var("LASTFM_USERNAME")?;
+    let lastfm_users: Vec<String> = std::env::var("LASTFM_USERNAME")
+        .map(|raw| raw.split(',').map(|u| u.trim().to_string()).filter(|u| !u.is_empty()).collect())
+        .unwrap_or_default();
+    let spotify_seed_url = std::env::var("SPOTIFY_SEED_URL").ok();
     let spotify_client_id = std::env::var("SPOTIFY_CLIENT_ID")?;
     let spotify_client_secret = std::env::var("SPOTIFY_CLIENT_SECRET")?;
     let spotify_user_id = std::env::var("SPOTIFY_USER_ID")?;
-    
+    let top_time_range = std::env::var("SPOTIFY_TOP_TIME_RANGE")
+        .ok()
+        .map(|raw| TopTimeRange::parse(&raw).ok_or(format!("Invalid SPOTIFY_TOP_TIME_RANGE: {}", raw)))
+        .transpose()?
+        .unwrap_or(TopTimeRange::Medium);
+    let full_album_mode = std::env::var("SPOTIFY_FULL_ALBUM_MODE")
+        .map(|raw| raw == "1" || raw.eq_ignore_ascii_case("true"))
+        .unwrap_or(false);
+
+    if lastfm_users.is_empty() && spotify_seed_url.is_none() {
+        return Err("Set either LASTFM_USERNAME or SPOTIFY_SEED_URL".into());
+    }
+
     // Use a fixed redirect URI - add this to your Spotify app settings
     let redirect_uri = "http://localhost:8888/callback";
-    
+
     let client = Client::new();
-    
-    // Get recommendations
-    let recommendations = get_recommendations(&client, &lastfm_user, &lastfm_key).await?;
-    
+
+    // Get authorized token using OAuth flow
+    println!("\n🔐 Starting Spotify authorization...");
+    let spotify_token = get_spotify_auth_token(
+        &spotify_client_id,
+        &spotify_client_secret,
+        redirect_uri,
+    ).await?;
+
+    // Get recommendations: a pasted Spotify share link, a blend of multiple
+    // Last.fm listeners, or a single listener's Last.fm history.
+    let (recommendations, playlist_name, playlist_description) = if let Some(seed_url) = &spotify_seed_url {
+        let (kind, id) = parse_spotify_seed_url(seed_url)
+            .ok_or("Could not parse SPOTIFY_SEED_URL as a track/album/artist link")?;
+        let seed_artist = resolve_seed_artist_name(&client, &spotify_token, kind, &id).await?;
+        println!("🌱 Seeding recommendations from: {}", seed_artist);
+        let recommendations = get_recommendations_from_artist(&client, &seed_artist, &lastfm_key).await?;
+        (
+            recommendations,
+            format!("Last.fm Discoveries - {}", chrono::Local::now().format("%Y-%m-%d")),
+            format!("Fresh music recommendations seeded from {}", seed_artist),
+        )
+    } else if lastfm_users.len() >= 2 {
+        println!("🤝 Blending taste across: {}", lastfm_users.join(", "));
+        let recommendations = get_blended_recommendations(&client, &lastfm_users, &lastfm_key).await?;
+        (
+            recommendations,
+            format!("{} Shared Discoveries", lastfm_users.join(" & ")),
+            format!("Music loved across {}'s shared taste", lastfm_users.join(", ")),
+        )
+    } else {
+        println!("🎧 Fetching your Spotify top artists and tracks...");
+        let mut extra_seed_artists = get_spotify_top_artists(&client, &spotify_token, top_time_range).await?;
+        extra_seed_artists.extend(get_spotify_top_track_artists(&client, &spotify_token, top_time_range).await?);
+
+        let recommendations =
+            get_recommendations(&client, &lastfm_users[0], &lastfm_key, &extra_seed_artists).await?;
+        (
+            recommendations,
+            format!("Last.fm Discoveries - {}", chrono::Local::now().format("%Y-%m-%d")),
+            "Fresh music recommendations based on your Last.fm history".to_string(),
+        )
+    };
+
     if recommendations.is_empty() {
         println!("❌ Couldn't find any recommendations.");
         return Ok(());
     }
-    
+
     println!("\n✨ Found these recommendations:");
     for (i, (artist, album)) in recommendations.iter().enumerate() {
         println!("{}. {} - {}", i + 1, artist, album);
     }
-    
-    // Get authorized token using OAuth flow
-    println!("\n🔐 Starting Spotify authorization...");
-    let spotify_token = get_spotify_auth_token(
-        &spotify_client_id,
-        &spotify_client_secret,
-        redirect_uri,
-    ).await?;
 
     // Create the playlist
-    match create_spotify_playlist(&spotify_token, &spotify_user_id, &recommendations).await {
+    match create_spotify_playlist(&spotify_token, &spotify_user_id, &playlist_name, &playlist_description, &recommendations, full_album_mode).await {
         Ok(playlist_url) => {
             println!("\n✅ Successfully created Spotify playlist!");
             println!("🎵 Open your playlist here: {}", playlist_url);